@@ -0,0 +1,7 @@
+// Same facade-over-same-named-submodule shape as `src/rc.rs`.
+#[allow(clippy::module_inception)]
+pub mod cell;
+pub mod once;
+
+pub use cell::{BorrowError, BorrowMutError, Cell, Ref, RefCell, RefMut, UnsafeCell};
+pub use once::{LazyCell, OnceCell};
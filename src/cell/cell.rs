@@ -95,6 +95,18 @@ impl<T: ?Sized> Cell<T> {
     }
 }
 
+impl<T> Cell<[T]> {
+    /// Returns a `&[Cell<T>]` view over this `Cell<[T]>`, letting callers
+    /// mutate individual elements of a shared slice without a `RefCell`.
+    ///
+    /// `Cell<T>` is `#[repr(transparent)]` over `UnsafeCell<T>`, which is
+    /// itself `#[repr(transparent)]` over `T`, so `&Cell<[T]>` and
+    /// `&[Cell<T>]` share the same layout and can be reinterpreted in place.
+    pub fn as_slice_of_cells(&self) -> &[Cell<T>] {
+        unsafe { &*(self as *const Cell<[T]> as *const [Cell<T>]) }
+    }
+}
+
 impl<T: Copy> Cell<T> {
     pub fn get(&self) -> T {
         unsafe {
@@ -138,6 +150,34 @@ enum RefState {
     Exclusive,
 }
 
+/// An error returned by [`RefCell::try_borrow`].
+#[derive(Debug, Clone)]
+pub struct BorrowError {
+    _private: (),
+}
+
+impl std::fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("already mutably borrowed")
+    }
+}
+
+impl std::error::Error for BorrowError {}
+
+/// An error returned by [`RefCell::try_borrow_mut`].
+#[derive(Debug, Clone)]
+pub struct BorrowMutError {
+    _private: (),
+}
+
+impl std::fmt::Display for BorrowMutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("already borrowed")
+    }
+}
+
+impl std::error::Error for BorrowMutError {}
+
 pub struct RefCell<T> {
     value: UnsafeCell<T>,
     state: Cell<RefState>,
@@ -155,87 +195,140 @@ impl<T> RefCell<T> {
 }
 
 impl<T> RefCell<T> {
-    pub fn borrow(&self) -> Option<Ref<'_, T>> {
+    pub fn try_borrow(&self) -> Result<Ref<'_, T>, BorrowError> {
         match self.state.get() {
             RefState::Unshared => {
                 self.state.set(RefState::Shared(1));
 
-                Some(Ref {
-                    ref_cell: self,
+                Ok(Ref {
+                    value: self.value.get(),
+                    state: &self.state,
                 })
             }
             RefState::Shared(n) => {
                 self.state.set(RefState::Shared(n + 1));
 
-                Some(Ref {
-                    ref_cell: self,
+                Ok(Ref {
+                    value: self.value.get(),
+                    state: &self.state,
                 })
             }
-            _ => None,
+            RefState::Exclusive => Err(BorrowError { _private: () }),
         }
     }
 
-    pub fn borrow_mut(&self) -> Option<RefMut<'_, T>> {
-        if let RefState::Unshared = self.state.get() {
-            self.state.set(RefState::Exclusive);
+    /// Like [`RefCell::try_borrow`], but panics instead of returning an error
+    /// if the value is currently mutably borrowed.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.try_borrow().expect("already mutably borrowed")
+    }
 
-            Some(RefMut {
-                refcell: self,
-            })
-        } else {
-            None
+    pub fn try_borrow_mut(&self) -> Result<RefMut<'_, T>, BorrowMutError> {
+        match self.state.get() {
+            RefState::Unshared => {
+                self.state.set(RefState::Exclusive);
+
+                Ok(RefMut {
+                    value: self.value.get(),
+                    state: &self.state,
+                })
+            }
+            _ => Err(BorrowMutError { _private: () }),
         }
     }
+
+    /// Like [`RefCell::try_borrow_mut`], but panics instead of returning an
+    /// error if the value is currently borrowed.
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.try_borrow_mut().expect("already borrowed")
+    }
 }
 
 pub struct Ref<'refcell, T> {
-    ref_cell: &'refcell RefCell<T>,
+    value: *const T,
+    state: &'refcell Cell<RefState>,
+}
+
+impl<'refcell, T> Ref<'refcell, T> {
+    /// Projects a `Ref` to a borrow of a component of the wrapped value,
+    /// e.g. a field, keeping the original borrow's state tied to the field.
+    pub fn map<U, F>(orig: Ref<'refcell, T>, f: F) -> Ref<'refcell, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        let value = f(unsafe { &*orig.value }) as *const U;
+        let state = orig.state;
+
+        // The mapped `Ref` now owns the borrow that `orig` was holding.
+        std::mem::forget(orig);
+
+        Ref { value, state }
+    }
 }
 
 impl<T> std::ops::Deref for Ref<'_, T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
-        unsafe { &*self.ref_cell.value.get() }
+        unsafe { &*self.value }
     }
 }
 
 impl<T> Drop for Ref<'_, T> {
     fn drop(&mut self) {
-        match self.ref_cell.state.get() {
+        match self.state.get() {
             RefState::Exclusive | RefState::Unshared => unreachable!(),
             RefState::Shared(1) => {
-                self.ref_cell.state.set(RefState::Unshared);
+                self.state.set(RefState::Unshared);
             }
             RefState::Shared(n) => {
-                self.ref_cell.state.set(RefState::Shared(n - 1));
+                self.state.set(RefState::Shared(n - 1));
             }
         }
     }
 }
 
 pub struct RefMut<'refcell, T> {
-    refcell: &'refcell RefCell<T>,
+    value: *mut T,
+    state: &'refcell Cell<RefState>,
+}
+
+impl<'refcell, T> RefMut<'refcell, T> {
+    /// Projects a `RefMut` to a mutable borrow of a component of the wrapped
+    /// value, e.g. a field, keeping the original borrow's state tied to the
+    /// field.
+    pub fn map<U, F>(orig: RefMut<'refcell, T>, f: F) -> RefMut<'refcell, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let value = f(unsafe { &mut *orig.value }) as *mut U;
+        let state = orig.state;
+
+        // The mapped `RefMut` now owns the borrow that `orig` was holding.
+        std::mem::forget(orig);
+
+        RefMut { value, state }
+    }
 }
 
 impl<T> std::ops::Deref for RefMut<'_, T> {
     type Target = T;
     fn deref(&self) -> &Self::Target {
-        unsafe { &*self.refcell.value.get() }
+        unsafe { &*self.value }
     }
 }
 
 impl<T> std::ops::DerefMut for RefMut<'_, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { &mut *self.refcell.value.get() }
+        unsafe { &mut *self.value }
     }
 }
 
 impl<T> Drop for RefMut<'_, T> {
     fn drop(&mut self) {
-        match self.refcell.state.get() {
+        match self.state.get() {
             RefState::Shared(_) | RefState::Unshared => unreachable!(),
             RefState::Exclusive => {
-                self.refcell.state.set(RefState::Unshared);
+                self.state.set(RefState::Unshared);
             }
         }
     }
@@ -243,8 +336,7 @@ impl<T> Drop for RefMut<'_, T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::cell::{Cell, RefCell};
-    use crate::rc::Rc;
+    use crate::cell::{Cell, Ref, RefCell, RefMut};
 
     #[test]
     fn test_cell() {
@@ -253,15 +345,67 @@ mod tests {
         println!("{}", t.get());
     }
 
+    #[test]
+    fn test_as_slice_of_cells() {
+        let mut values = [1, 2, 3, 4];
+        let cell = Cell::from_mut(&mut values[..]);
+        let slice_of_cells = cell.as_slice_of_cells();
+
+        for c in slice_of_cells.iter().step_by(2) {
+            c.set(c.get() * 10);
+        }
+
+        assert_eq!(values, [10, 2, 30, 4]);
+    }
+
     #[test]
     fn test_ref_cell() {
         let s = RefCell::new(String::from("abc"));
 
-        let mut t = s.borrow_mut().unwrap();
+        let mut t = s.borrow_mut();
         t.push_str("acv");
 
         drop(t);
 
-        assert_eq!(s.borrow().unwrap().to_uppercase(), "ABCACV");
+        assert_eq!(s.borrow().to_uppercase(), "ABCACV");
+    }
+
+    #[test]
+    fn test_try_borrow_conflict() {
+        let s = RefCell::new(5);
+
+        let _r1 = s.try_borrow().unwrap();
+        let _r2 = s.try_borrow().unwrap();
+        assert!(s.try_borrow_mut().is_err());
+
+        drop(_r1);
+        drop(_r2);
+
+        let _m = s.try_borrow_mut().unwrap();
+        assert!(s.try_borrow().is_err());
+    }
+
+    #[test]
+    fn test_ref_map_projects_field() {
+        struct Pair {
+            first: String,
+            second: String,
+        }
+
+        let cell = RefCell::new(Pair {
+            first: String::from("a"),
+            second: String::from("b"),
+        });
+
+        let first = Ref::map(cell.borrow(), |p| &p.first);
+        assert_eq!(&*first, "a");
+        drop(first);
+
+        {
+            let mut second = RefMut::map(cell.borrow_mut(), |p| &mut p.second);
+            second.push('!');
+        }
+
+        assert_eq!(cell.borrow().second, "b!");
     }
 }
\ No newline at end of file
@@ -0,0 +1,195 @@
+use crate::cell::{Cell, UnsafeCell};
+
+#[derive(Clone, Copy, PartialEq)]
+enum OnceState {
+    Uninit,
+    Running,
+    Init,
+}
+
+/// A cell that can be written to at most once.
+pub struct OnceCell<T> {
+    inner: UnsafeCell<Option<T>>,
+    state: Cell<OnceState>,
+}
+
+impl<T> OnceCell<T> {
+    pub fn new() -> Self {
+        Self {
+            inner: UnsafeCell::new(None),
+            state: Cell::new(OnceState::Uninit),
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        unsafe { &*self.inner.get() }.as_ref()
+    }
+
+    /// Sets the value, failing with the value back if the cell was already
+    /// written to or is being initialized by `get_or_init`.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        if self.state.get() != OnceState::Uninit {
+            return Err(value);
+        }
+
+        unsafe {
+            *self.inner.get() = Some(value);
+        }
+        self.state.set(OnceState::Init);
+
+        Ok(())
+    }
+
+    /// Returns the value, initializing it with `f` if this is the first
+    /// call. Panics if `f` calls `get_or_init` on this same cell while it
+    /// runs.
+    ///
+    /// If `f` panics, the cell is left `Uninit` rather than stuck `Running`,
+    /// so a later call can retry initialization instead of forever hitting
+    /// the reentrancy panic above.
+    pub fn get_or_init<F>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        match self.state.get() {
+            OnceState::Init => return self.get().unwrap(),
+            OnceState::Running => panic!("reentrant init of OnceCell"),
+            OnceState::Uninit => {}
+        }
+
+        self.state.set(OnceState::Running);
+
+        // Resets `state` back to `Uninit` on unwind; disarmed once `f`
+        // returns so the happy path can set `Init` itself.
+        struct ResetOnUnwind<'a>(&'a Cell<OnceState>);
+        impl Drop for ResetOnUnwind<'_> {
+            fn drop(&mut self) {
+                self.0.set(OnceState::Uninit);
+            }
+        }
+        let guard = ResetOnUnwind(&self.state);
+
+        let value = f();
+        std::mem::forget(guard);
+
+        unsafe {
+            *self.inner.get() = Some(value);
+        }
+        self.state.set(OnceState::Init);
+
+        self.get().unwrap()
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A value that is computed on first access and cached for every access
+/// after that.
+pub struct LazyCell<T, F = fn() -> T> {
+    cell: OnceCell<T>,
+    init: Cell<Option<F>>,
+}
+
+impl<T, F> LazyCell<T, F> {
+    pub fn new(init: F) -> Self {
+        Self {
+            cell: OnceCell::new(),
+            init: Cell::new(Some(init)),
+        }
+    }
+}
+
+impl<T, F: FnOnce() -> T> LazyCell<T, F> {
+    /// Forces evaluation, running the initializer at most once.
+    pub fn force(this: &Self) -> &T {
+        this.cell.get_or_init(|| {
+            let init = this
+                .init
+                .take()
+                .expect("LazyCell initializer already consumed");
+            init()
+        })
+    }
+}
+
+impl<T, F: FnOnce() -> T> std::ops::Deref for LazyCell<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        LazyCell::force(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LazyCell, OnceCell};
+    use crate::cell::Cell;
+
+    #[test]
+    fn test_once_cell_set_and_get() {
+        let cell = OnceCell::new();
+        assert_eq!(cell.get(), None);
+
+        assert_eq!(cell.set(5), Ok(()));
+        assert_eq!(cell.set(6), Err(6));
+        assert_eq!(cell.get(), Some(&5));
+    }
+
+    #[test]
+    fn test_once_cell_get_or_init_runs_once() {
+        let calls = Cell::new(0);
+        let cell = OnceCell::new();
+
+        let a = cell.get_or_init(|| {
+            calls.set(calls.get() + 1);
+            42
+        });
+        assert_eq!(*a, 42);
+
+        let b = cell.get_or_init(|| {
+            calls.set(calls.get() + 1);
+            99
+        });
+        assert_eq!(*b, 42);
+
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "reentrant init")]
+    fn test_once_cell_reentrant_init_panics() {
+        let cell: OnceCell<i32> = OnceCell::new();
+        cell.get_or_init(|| *cell.get_or_init(|| 1));
+    }
+
+    #[test]
+    fn test_once_cell_recovers_after_panicking_init() {
+        let cell: OnceCell<i32> = OnceCell::new();
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            cell.get_or_init(|| panic!("init blew up"))
+        }));
+        assert!(result.is_err());
+
+        // A failed init must not leave the cell permanently `Running`: a
+        // retry should succeed instead of hitting "reentrant init".
+        assert_eq!(*cell.get_or_init(|| 7), 7);
+    }
+
+    #[test]
+    fn test_lazy_cell_forces_once() {
+        let calls = Cell::new(0);
+        let lazy = LazyCell::new(|| {
+            calls.set(calls.get() + 1);
+            String::from("hi")
+        });
+
+        assert_eq!(&*lazy, "hi");
+        assert_eq!(&*lazy, "hi");
+        assert_eq!(calls.get(), 1);
+    }
+}
@@ -1,5 +1,6 @@
 #![allow(unused)]
 #![feature(negative_impls)]
+#![feature(coerce_unsized, unsize)]
 
 mod rc;
 mod cell;
@@ -0,0 +1,9 @@
+// `rc` (this module) re-exports an inner submodule of the same name, which
+// trips clippy::module_inception; the split keeps `Rc`/`Weak` and `Arc`
+// their own files under src/rc/ while this file stays the public facade.
+#[allow(clippy::module_inception)]
+pub mod rc;
+mod arc;
+
+pub use rc::{Rc, Weak};
+pub use arc::{Arc, Weak as ArcWeak};
@@ -1,58 +1,82 @@
-use std::marker::PhantomData;
+use std::alloc::{self, Layout};
+use std::marker::{PhantomData, Unsize};
+use std::mem::ManuallyDrop;
+use std::ops::CoerceUnsized;
+use std::ptr;
 use std::ptr::NonNull;
 use std::sync::atomic;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub struct Arc<T> {
-    ptr: NonNull<ArcInner<T>>,
-    _marker: PhantomData<ArcInner<T>>,
+#[repr(C)]
+pub struct ArcInner<T: ?Sized> {
+    strong: AtomicUsize,
+    // The allocation itself counts as one weak reference, shared by every
+    // live `Arc`, so the backing `Box` stays alive for `Weak` pointers even
+    // after the last strong reference has dropped `data`.
+    weak: AtomicUsize,
+    // Must stay last: it's the only field allowed to be unsized.
+    data: ManuallyDrop<T>,
 }
 
-pub struct ArcInner<T> {
-    rc: AtomicUsize,
-    data: T,
+pub struct Arc<T: ?Sized> {
+    ptr: NonNull<ArcInner<T>>,
+    _marker: PhantomData<ArcInner<T>>,
 }
 
 impl<T> Arc<T> {
     pub fn new(data: T) -> Arc<T> {
         Arc {
-            ptr: NonNull::new(
-                Box::into_raw(
-                    Box::new(
-                        ArcInner {
-                            rc: AtomicUsize::new(1),
-                            data,
-                        }
-                    )
-                )
-            ).unwrap(),
+            ptr: NonNull::new(Box::into_raw(Box::new(ArcInner {
+                strong: AtomicUsize::new(1),
+                weak: AtomicUsize::new(1),
+                data: ManuallyDrop::new(data),
+            })))
+            .unwrap(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Arc<T> {
+    pub fn strong_count(this: &Self) -> usize {
+        unsafe { this.ptr.as_ref() }.strong.load(Ordering::SeqCst)
+    }
+
+    pub fn weak_count(this: &Self) -> usize {
+        // Don't count the implicit weak reference held by the strong refs.
+        unsafe { this.ptr.as_ref() }.weak.load(Ordering::SeqCst) - 1
+    }
+
+    /// Creates a new `Weak` pointer to this allocation.
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        let inner = unsafe { this.ptr.as_ref() };
+        inner.weak.fetch_add(1, Ordering::Relaxed);
+
+        Weak {
+            ptr: this.ptr,
             _marker: PhantomData,
         }
     }
 }
 
-unsafe impl<T: Sync + Send> Send for Arc<T> {}
-unsafe impl<T: Sync + Send> Sync for Arc<T> {}
+unsafe impl<T: ?Sized + Sync + Send> Send for Arc<T> {}
+unsafe impl<T: ?Sized + Sync + Send> Sync for Arc<T> {}
 
-impl<T> std::ops::Deref for Arc<T> {
+impl<T: ?Sized> std::ops::Deref for Arc<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &unsafe {
-            self.ptr.as_ref()
-        }.data
+        &unsafe { self.ptr.as_ref() }.data
     }
 }
 
-impl<T> Clone for Arc<T> {
+impl<T: ?Sized> Clone for Arc<T> {
     fn clone(&self) -> Self {
-        let inner = unsafe {
-            self.ptr.as_ref()
-        };
+        let inner = unsafe { self.ptr.as_ref() };
 
-        let old_rc = inner.rc.fetch_add(1, Ordering::Relaxed);
+        let old_strong = inner.strong.fetch_add(1, Ordering::Relaxed);
 
-        if old_rc >= isize::MAX as usize {
+        if old_strong >= isize::MAX as usize {
             std::process::abort();
         }
 
@@ -63,20 +87,324 @@ impl<T> Clone for Arc<T> {
     }
 }
 
-impl<T> Drop for Arc<T> {
+impl<T: ?Sized> Drop for Arc<T> {
     fn drop(&mut self) {
-        let inner = unsafe {
-            self.ptr.as_ref()
-        };
+        let inner = unsafe { self.ptr.as_ref() };
 
-        if inner.rc.fetch_sub(1, Ordering::Release) != 1 {
+        if inner.strong.fetch_sub(1, Ordering::Release) != 1 {
             return;
         }
 
         atomic::fence(Ordering::Acquire);
 
+        // Must be `ManuallyDrop::drop`, not `ptr::drop_in_place`: `data`'s
+        // type is `ManuallyDrop<T>`, and `drop_in_place` on a `ManuallyDrop`
+        // is a no-op by design, so using it here would silently leak `T`.
         unsafe {
-            let _ = Box::from_raw(self.ptr.as_ptr());
+            ManuallyDrop::drop(&mut (*self.ptr.as_ptr()).data);
         }
+
+        drop_weak_ref(self.ptr);
     }
-}
\ No newline at end of file
+}
+
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Arc<U>> for Arc<T> {}
+
+/// Releases one weak reference, freeing the allocation once none remain.
+fn drop_weak_ref<T: ?Sized>(ptr: NonNull<ArcInner<T>>) {
+    let inner = unsafe { ptr.as_ref() };
+
+    if inner.weak.fetch_sub(1, Ordering::Release) != 1 {
+        return;
+    }
+
+    atomic::fence(Ordering::Acquire);
+
+    unsafe {
+        let layout = Layout::for_value(ptr.as_ref());
+        alloc::dealloc(ptr.as_ptr() as *mut u8, layout);
+    }
+}
+
+pub struct Weak<T: ?Sized> {
+    ptr: NonNull<ArcInner<T>>,
+    _marker: PhantomData<ArcInner<T>>,
+}
+
+unsafe impl<T: ?Sized + Sync + Send> Send for Weak<T> {}
+unsafe impl<T: ?Sized + Sync + Send> Sync for Weak<T> {}
+
+impl<T: ?Sized> Weak<T> {
+    /// Attempts to upgrade this `Weak` pointer to an `Arc`.
+    ///
+    /// This is lock-free: it loads `strong` and, as long as it hasn't
+    /// dropped to zero, races to bump it with a CAS loop so an upgrade
+    /// racing against the final `Arc::drop` is rejected rather than
+    /// resurrecting an already-dropped value.
+    pub fn upgrade(&self) -> Option<Arc<T>> {
+        let inner = unsafe { self.ptr.as_ref() };
+
+        let mut strong = inner.strong.load(Ordering::Relaxed);
+
+        loop {
+            if strong == 0 {
+                return None;
+            }
+
+            if strong >= isize::MAX as usize {
+                std::process::abort();
+            }
+
+            match inner.strong.compare_exchange_weak(
+                strong,
+                strong + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    return Some(Arc {
+                        ptr: self.ptr,
+                        _marker: PhantomData,
+                    })
+                }
+                Err(actual) => strong = actual,
+            }
+        }
+    }
+}
+
+impl<T: ?Sized> Clone for Weak<T> {
+    fn clone(&self) -> Self {
+        let inner = unsafe { self.ptr.as_ref() };
+        inner.weak.fetch_add(1, Ordering::Relaxed);
+
+        Weak {
+            ptr: self.ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for Weak<T> {
+    fn drop(&mut self) {
+        drop_weak_ref(self.ptr);
+    }
+}
+
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Weak<U>> for Weak<T> {}
+
+#[repr(C)]
+struct ArcInnerHeader {
+    strong: AtomicUsize,
+    weak: AtomicUsize,
+}
+
+/// Allocates (uninitialized) room for an `ArcInner<[T]>` of `len` elements;
+/// see `rc::allocate_rc_inner_slice` for why the fat-pointer reinterpret
+/// cast below is sound.
+fn allocate_arc_inner_slice<T>(len: usize) -> NonNull<ArcInner<[T]>> {
+    let layout = Layout::new::<ArcInnerHeader>()
+        .extend(Layout::array::<T>(len).expect("slice layout overflow"))
+        .expect("layout overflow")
+        .0
+        .pad_to_align();
+
+    let raw = unsafe { alloc::alloc(layout) };
+    if raw.is_null() {
+        alloc::handle_alloc_error(layout);
+    }
+
+    let fat: *mut [T] = ptr::slice_from_raw_parts_mut(raw as *mut T, len);
+    unsafe { NonNull::new_unchecked(fat as *mut ArcInner<[T]>) }
+}
+
+impl<T> From<Box<[T]>> for Arc<[T]> {
+    /// Builds an `Arc<[T]>` that owns its own copy of `v`'s elements, freeing
+    /// `v`'s original allocation once they've been moved over.
+    fn from(v: Box<[T]>) -> Self {
+        let len = v.len();
+        let src_data = Box::into_raw(v) as *mut T;
+
+        let inner = allocate_arc_inner_slice::<T>(len);
+
+        unsafe {
+            ptr::write(
+                ptr::addr_of_mut!((*inner.as_ptr()).strong),
+                AtomicUsize::new(1),
+            );
+            ptr::write(
+                ptr::addr_of_mut!((*inner.as_ptr()).weak),
+                AtomicUsize::new(1),
+            );
+
+            let dst_data = ptr::addr_of_mut!((*inner.as_ptr()).data) as *mut T;
+            ptr::copy_nonoverlapping(src_data, dst_data, len);
+
+            alloc::dealloc(src_data as *mut u8, Layout::array::<T>(len).unwrap());
+        }
+
+        Arc {
+            ptr: inner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone> From<&[T]> for Arc<[T]> {
+    fn from(slice: &[T]) -> Self {
+        let len = slice.len();
+        let inner = allocate_arc_inner_slice::<T>(len);
+
+        unsafe {
+            ptr::write(
+                ptr::addr_of_mut!((*inner.as_ptr()).strong),
+                AtomicUsize::new(1),
+            );
+            ptr::write(
+                ptr::addr_of_mut!((*inner.as_ptr()).weak),
+                AtomicUsize::new(1),
+            );
+
+            let dst_data = ptr::addr_of_mut!((*inner.as_ptr()).data) as *mut T;
+            for (i, item) in slice.iter().enumerate() {
+                ptr::write(dst_data.add(i), item.clone());
+            }
+        }
+
+        Arc {
+            ptr: inner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rc::arc::{Arc, Weak};
+    use crate::rc::{Arc as FacadeArc, ArcWeak};
+    use std::fmt::Display;
+    use std::thread;
+
+    #[test]
+    fn test_arc() {
+        let a = Arc::new(5);
+        let b = a.clone();
+
+        assert_eq!(Arc::strong_count(&a), 2);
+        assert_eq!(*b, 5);
+    }
+
+    #[test]
+    fn test_weak_upgrade_after_drop() {
+        let a = Arc::new(String::from("hello"));
+        let w = Arc::downgrade(&a);
+
+        assert_eq!(Arc::weak_count(&a), 1);
+        assert!(w.upgrade().is_some());
+
+        drop(a);
+
+        assert!(w.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_concurrent_downgrade_upgrade_race() {
+        let a = Arc::new(42);
+        let w = Arc::downgrade(&a);
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let w = w.clone();
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        if let Some(upgraded) = w.upgrade() {
+                            assert_eq!(*upgraded, 42);
+                            let _w2 = Arc::downgrade(&upgraded);
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        drop(a);
+
+        for h in handles {
+            h.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_arc_weak_reachable_through_public_facade() {
+        use crate::cell::RefCell;
+
+        // Exercises `ArcWeak` through `crate::rc`'s public path rather than
+        // reaching into `crate::rc::arc` directly: a struct field holding a
+        // `Weak<T>` only compiles if the facade re-exports `arc::Weak`
+        // under a name distinct from `rc::Weak`.
+        struct Node {
+            parent: RefCell<Option<ArcWeak<Node>>>,
+            children: RefCell<Vec<FacadeArc<Node>>>,
+        }
+
+        let leaf = FacadeArc::new(Node {
+            parent: RefCell::new(None),
+            children: RefCell::new(vec![]),
+        });
+
+        let branch = FacadeArc::new(Node {
+            parent: RefCell::new(None),
+            children: RefCell::new(vec![FacadeArc::clone(&leaf)]),
+        });
+
+        *leaf.parent.borrow_mut() = Some(FacadeArc::downgrade(&branch));
+
+        assert!(leaf.parent.borrow().as_ref().unwrap().upgrade().is_some());
+
+        drop(branch);
+
+        assert!(leaf.parent.borrow().as_ref().unwrap().upgrade().is_none());
+    }
+
+    #[test]
+    fn test_coerce_to_dyn_trait() {
+        let a: Arc<dyn Display + Send + Sync> = Arc::new(String::from("coerced"));
+        assert_eq!(a.to_string(), "coerced");
+    }
+
+    #[test]
+    fn test_arc_slice_from_vec() {
+        let v = vec![1u32, 2, 3, 4];
+        let a: Arc<[u32]> = Arc::from(v.into_boxed_slice());
+
+        assert_eq!(&*a, [1, 2, 3, 4]);
+        assert_eq!(Arc::strong_count(&a), 1);
+    }
+
+    #[test]
+    fn test_drop_runs_value_destructor() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        struct Noisy<'a>(&'a AtomicBool);
+        impl Drop for Noisy<'_> {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let dropped = AtomicBool::new(false);
+        let a = Arc::new(Noisy(&dropped));
+        let b = a.clone();
+
+        drop(a);
+        assert!(
+            !dropped.load(Ordering::SeqCst),
+            "value must not drop while a clone is live"
+        );
+
+        drop(b);
+        assert!(
+            dropped.load(Ordering::SeqCst),
+            "last Arc dropped without running T's destructor"
+        );
+    }
+}
@@ -1,23 +1,33 @@
-use std::marker::PhantomData;
-use std::ops::Deref;
+use std::alloc::{self, Layout};
+use std::marker::{PhantomData, Unsize};
+use std::mem::ManuallyDrop;
+use std::ops::{CoerceUnsized, Deref};
+use std::ptr;
 use std::ptr::NonNull;
 use crate::cell::Cell;
 
-pub struct Rc<T> {
-    inner: NonNull<RcInner<T>>,
-    _marker: PhantomData<RcInner<T>>,
+#[repr(C)]
+struct RcInner<T: ?Sized> {
+    strong: Cell<usize>,
+    // The allocation itself is always counted as one weak reference, shared
+    // by every live `Rc`. This keeps the backing memory alive for `Weak`
+    // pointers to inspect even after `value` has been dropped.
+    weak: Cell<usize>,
+    // Must stay last: it's the only field allowed to be unsized.
+    value: ManuallyDrop<T>,
 }
 
-struct RcInner<T> {
-    value: T,
-    ref_count: Cell<usize>,
+pub struct Rc<T: ?Sized> {
+    inner: NonNull<RcInner<T>>,
+    _marker: PhantomData<RcInner<T>>,
 }
 
 impl<T> Rc<T> {
     pub fn new(v: T) -> Self {
         let leaked = Box::leak(Box::new(RcInner {
-            value: v,
-            ref_count: Cell::new(1),
+            strong: Cell::new(1),
+            weak: Cell::new(1),
+            value: ManuallyDrop::new(v),
         }));
 
         Self {
@@ -25,34 +35,91 @@ impl<T> Rc<T> {
             _marker: PhantomData,
         }
     }
+}
 
-    pub fn count(this: &Self) -> usize {
-        unsafe {
-            this.inner.as_ref().ref_count.get()
+impl<T: ?Sized> Rc<T> {
+    pub fn strong_count(this: &Self) -> usize {
+        unsafe { this.inner.as_ref().strong.get() }
+    }
+
+    pub fn weak_count(this: &Self) -> usize {
+        // Don't count the implicit weak reference held by the strong refs.
+        unsafe { this.inner.as_ref().weak.get() - 1 }
+    }
+
+    /// Creates a new `Weak` pointer to this allocation.
+    pub fn downgrade(this: &Self) -> Weak<T> {
+        let inner = unsafe { this.inner.as_ref() };
+        inner.weak.set(inner.weak.get() + 1);
+
+        Weak {
+            inner: this.inner,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a mutable reference to the inner value, but only if there are
+    /// no other `Rc` or `Weak` pointers to it.
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if Self::strong_count(this) == 1 && Self::weak_count(this) == 0 {
+            unsafe { Some(&mut (*this.inner.as_ptr()).value) }
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Rc<T> {
+    /// Moves the inner value out, if this is the only strong reference to
+    /// it. Otherwise hands `this` back unchanged.
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if Self::strong_count(&this) != 1 {
+            return Err(this);
+        }
+
+        let inner = this.inner;
+        let value = unsafe { ptr::read(&*inner.as_ref().value) };
+
+        unsafe { inner.as_ref().strong.set(0) };
+        drop_weak_ref(inner);
+
+        // The value has already been moved out and the counts updated by
+        // hand, so skip `Rc`'s own `Drop` impl.
+        std::mem::forget(this);
+
+        Ok(value)
+    }
+}
+
+impl<T: Clone> Rc<T> {
+    /// Returns a mutable reference to the inner value, cloning it into a
+    /// fresh allocation first if it is shared with other `Rc`/`Weak`
+    /// pointers.
+    pub fn make_mut(this: &mut Self) -> &mut T {
+        if Self::strong_count(this) != 1 || Self::weak_count(this) != 0 {
+            *this = Rc::new((**this).clone());
         }
+
+        unsafe { &mut (*this.inner.as_ptr()).value }
     }
 }
 
-impl<T> std::ops::Deref for Rc<T> {
+impl<T: ?Sized> Deref for Rc<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &unsafe {
-            self.inner.as_ref()
-        }.value
+        &unsafe { self.inner.as_ref() }.value
     }
 }
 
-impl<T> Clone for Rc<T> {
+impl<T: ?Sized> Clone for Rc<T> {
     fn clone(&self) -> Self {
-        let inner = unsafe {
-            self.inner.as_ref()
-        };
-        
-        let c = inner.ref_count.get();
-        
-        inner.ref_count.set(c + 1);
-        
+        let inner = unsafe { self.inner.as_ref() };
+
+        let c = inner.strong.get();
+
+        inner.strong.set(c + 1);
+
         Rc {
             inner: self.inner,
             _marker: PhantomData,
@@ -60,27 +127,186 @@ impl<T> Clone for Rc<T> {
     }
 }
 
-impl<T> Drop for Rc<T> {
+impl<T: ?Sized> Drop for Rc<T> {
     fn drop(&mut self) {
-        let inner = unsafe {
-            self.inner.as_ref()
-        };
+        let inner = unsafe { self.inner.as_ref() };
+
+        let strong = inner.strong.get();
+
+        if strong != 1 {
+            inner.strong.set(strong - 1);
+            return;
+        }
+
+        // This was the last strong reference: drop the value in place, but
+        // keep the allocation around for any `Weak` pointers still watching
+        // it, then release the implicit weak reference the strong refs held.
+        //
+        // Must be `ManuallyDrop::drop`, not `ptr::drop_in_place`: `value`'s
+        // type is `ManuallyDrop<T>`, and `drop_in_place` on a `ManuallyDrop`
+        // is a no-op by design, so using it here would silently leak `T`.
+        unsafe {
+            ManuallyDrop::drop(&mut (*self.inner.as_ptr()).value);
+        }
+        inner.strong.set(0);
+
+        drop_weak_ref(self.inner);
+    }
+}
+
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Rc<U>> for Rc<T> {}
+
+/// Releases one weak reference, freeing the allocation once none remain.
+fn drop_weak_ref<T: ?Sized>(inner: NonNull<RcInner<T>>) {
+    let weak = unsafe { inner.as_ref().weak.get() };
+
+    if weak == 1 {
+        unsafe {
+            dealloc_rc_inner(inner);
+        }
+    } else {
+        unsafe {
+            inner.as_ref().weak.set(weak - 1);
+        }
+    }
+}
+
+/// Frees the backing allocation for a `RcInner<T>` whose `value` has already
+/// been dropped (or moved out by `Rc::try_unwrap`).
+unsafe fn dealloc_rc_inner<T: ?Sized>(inner: NonNull<RcInner<T>>) {
+    let layout = Layout::for_value(inner.as_ref());
+    alloc::dealloc(inner.as_ptr() as *mut u8, layout);
+}
+
+pub struct Weak<T: ?Sized> {
+    inner: NonNull<RcInner<T>>,
+    _marker: PhantomData<RcInner<T>>,
+}
+
+impl<T: ?Sized> Weak<T> {
+    /// Attempts to upgrade this `Weak` pointer to an `Rc`, returning `None`
+    /// if the value has already been dropped.
+    pub fn upgrade(&self) -> Option<Rc<T>> {
+        let inner = unsafe { self.inner.as_ref() };
+
+        let strong = inner.strong.get();
+
+        if strong == 0 {
+            return None;
+        }
+
+        inner.strong.set(strong + 1);
+
+        Some(Rc {
+            inner: self.inner,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T: ?Sized> Clone for Weak<T> {
+    fn clone(&self) -> Self {
+        let inner = unsafe { self.inner.as_ref() };
+        inner.weak.set(inner.weak.get() + 1);
+
+        Weak {
+            inner: self.inner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for Weak<T> {
+    fn drop(&mut self) {
+        drop_weak_ref(self.inner);
+    }
+}
+
+impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<Weak<U>> for Weak<T> {}
+
+/// Allocates (uninitialized) room for a `RcInner<[T]>` of `len` elements,
+/// with `strong`/`weak` counters ahead of the slice data in one block.
+///
+/// `RcInner<[T]>`'s only unsized metadata is the element count of its tail
+/// `[T]`, same as a plain slice pointer, so a `*mut [T]` of the right length
+/// at this address can be reinterpreted directly as `*mut RcInner<[T]>`.
+fn allocate_rc_inner_slice<T>(len: usize) -> NonNull<RcInner<[T]>> {
+    let layout = Layout::new::<RcInnerHeader>()
+        .extend(Layout::array::<T>(len).expect("slice layout overflow"))
+        .expect("layout overflow")
+        .0
+        .pad_to_align();
+
+    let raw = unsafe { alloc::alloc(layout) };
+    if raw.is_null() {
+        alloc::handle_alloc_error(layout);
+    }
 
-        let c = inner.ref_count.get();
+    let fat: *mut [T] = ptr::slice_from_raw_parts_mut(raw as *mut T, len);
+    unsafe { NonNull::new_unchecked(fat as *mut RcInner<[T]>) }
+}
+
+#[repr(C)]
+struct RcInnerHeader {
+    strong: Cell<usize>,
+    weak: Cell<usize>,
+}
+
+impl<T> From<Box<[T]>> for Rc<[T]> {
+    /// Builds an `Rc<[T]>` that owns its own copy of `v`'s elements, freeing
+    /// `v`'s original allocation once they've been moved over.
+    fn from(v: Box<[T]>) -> Self {
+        let len = v.len();
+        let src_data = Box::into_raw(v) as *mut T;
+
+        let inner = allocate_rc_inner_slice::<T>(len);
 
         unsafe {
-            if c == 1 {
-                let _ = Box::from_raw(self.inner.as_ptr());
-            } else {
-                inner.ref_count.set(c - 1);
+            ptr::write(ptr::addr_of_mut!((*inner.as_ptr()).strong), Cell::new(1));
+            ptr::write(ptr::addr_of_mut!((*inner.as_ptr()).weak), Cell::new(1));
+
+            let dst_data = ptr::addr_of_mut!((*inner.as_ptr()).value) as *mut T;
+            ptr::copy_nonoverlapping(src_data, dst_data, len);
+
+            // The elements now live in `inner`; free the old backing memory
+            // without running `T`'s destructor a second time.
+            alloc::dealloc(src_data as *mut u8, Layout::array::<T>(len).unwrap());
+        }
+
+        Rc {
+            inner,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Clone> From<&[T]> for Rc<[T]> {
+    fn from(slice: &[T]) -> Self {
+        let len = slice.len();
+        let inner = allocate_rc_inner_slice::<T>(len);
+
+        unsafe {
+            ptr::write(ptr::addr_of_mut!((*inner.as_ptr()).strong), Cell::new(1));
+            ptr::write(ptr::addr_of_mut!((*inner.as_ptr()).weak), Cell::new(1));
+
+            let dst_data = ptr::addr_of_mut!((*inner.as_ptr()).value) as *mut T;
+            for (i, item) in slice.iter().enumerate() {
+                ptr::write(dst_data.add(i), item.clone());
             }
         }
+
+        Rc {
+            inner,
+            _marker: PhantomData,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::rc::rc::Rc;
+    use crate::cell::RefCell;
+    use crate::rc::rc::{Rc, Weak};
+    use std::fmt::Display;
 
     #[test]
     fn test_rc() {
@@ -88,10 +314,158 @@ mod tests {
         let b = t.clone();
         let s = b.clone();
 
-        assert_eq!(Rc::count(&s), 3);
+        assert_eq!(Rc::strong_count(&s), 3);
         assert_eq!(b.to_uppercase(), "ABCD");
         drop(s);
         drop(b);
-        assert_eq!(Rc::count(&t), 1);
+        assert_eq!(Rc::strong_count(&t), 1);
+    }
+
+    #[test]
+    fn test_weak_cycle_reclaimed() {
+        struct Node {
+            parent: RefCell<Option<Weak<Node>>>,
+            children: RefCell<Vec<Rc<Node>>>,
+        }
+
+        let leaf = Rc::new(Node {
+            parent: RefCell::new(None),
+            children: RefCell::new(vec![]),
+        });
+
+        assert_eq!(Rc::weak_count(&leaf), 0);
+
+        let branch = Rc::new(Node {
+            parent: RefCell::new(None),
+            children: RefCell::new(vec![Rc::clone(&leaf)]),
+        });
+
+        *leaf.parent.borrow_mut() = Some(Rc::downgrade(&branch));
+
+        assert_eq!(Rc::strong_count(&branch), 1);
+        assert_eq!(Rc::weak_count(&branch), 1);
+        assert_eq!(Rc::strong_count(&leaf), 2);
+
+        assert!(leaf.parent.borrow().as_ref().unwrap().upgrade().is_some());
+
+        drop(branch);
+
+        // The cycle is broken: leaf's extra strong ref (held by branch's
+        // children) is gone, and the parent pointer can no longer upgrade.
+        assert_eq!(Rc::strong_count(&leaf), 1);
+        assert!(leaf.parent.borrow().as_ref().unwrap().upgrade().is_none());
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut a = Rc::new(5);
+        *Rc::get_mut(&mut a).unwrap() += 1;
+        assert_eq!(*a, 6);
+
+        let b = a.clone();
+        assert!(Rc::get_mut(&mut a).is_none());
+        drop(b);
+        assert!(Rc::get_mut(&mut a).is_some());
+    }
+
+    #[test]
+    fn test_try_unwrap() {
+        let a = Rc::new(String::from("hi"));
+        let b = a.clone();
+
+        let a = match Rc::try_unwrap(a) {
+            Ok(_) => panic!("expected a shared Rc to be rejected"),
+            Err(a) => a,
+        };
+        drop(b);
+
+        match Rc::try_unwrap(a) {
+            Ok(value) => assert_eq!(value, "hi"),
+            Err(_) => panic!("expected unique ownership"),
+        }
+    }
+
+    #[test]
+    fn test_make_mut_clones_on_write() {
+        let mut a = Rc::new(vec![1, 2, 3]);
+        let b = a.clone();
+
+        Rc::make_mut(&mut a).push(4);
+
+        assert_eq!(*a, [1, 2, 3, 4]);
+        assert_eq!(*b, [1, 2, 3]);
+        assert_eq!(Rc::strong_count(&a), 1);
+
+        Rc::make_mut(&mut a).push(5);
+        assert_eq!(*a, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_coerce_to_dyn_trait() {
+        let a: Rc<dyn Display> = Rc::new(String::from("coerced"));
+        assert_eq!(a.to_string(), "coerced");
+    }
+
+    #[test]
+    fn test_rc_slice_from_vec() {
+        let v = vec![1u32, 2, 3, 4];
+        let a: Rc<[u32]> = Rc::from(v.into_boxed_slice());
+
+        assert_eq!(&*a, [1, 2, 3, 4]);
+
+        let b = a.clone();
+        assert_eq!(Rc::strong_count(&a), 2);
+        drop(b);
+    }
+
+    #[test]
+    fn test_rc_slice_from_ref() {
+        let a: Rc<[i32]> = Rc::from(&[10, 20, 30][..]);
+        assert_eq!(&*a, [10, 20, 30]);
+    }
+
+    #[test]
+    fn test_drop_runs_value_destructor() {
+        use crate::cell::Cell;
+
+        struct Noisy<'a>(&'a Cell<bool>);
+        impl Drop for Noisy<'_> {
+            fn drop(&mut self) {
+                self.0.set(true);
+            }
+        }
+
+        let dropped = Cell::new(false);
+        let a = Rc::new(Noisy(&dropped));
+        let b = a.clone();
+
+        drop(a);
+        assert!(!dropped.get(), "value must not drop while a clone is live");
+
+        drop(b);
+        assert!(dropped.get(), "last Rc dropped without running T's destructor");
+    }
+
+    #[test]
+    fn test_drop_runs_destructors_for_unsized_slice() {
+        use crate::cell::Cell;
+
+        struct Noisy<'a>(&'a Cell<usize>);
+        impl Drop for Noisy<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let v: Vec<Noisy> = (0..3).map(|_| Noisy(&drops)).collect();
+        let a: Rc<[Noisy]> = Rc::from(v.into_boxed_slice());
+
+        drop(a);
+        assert_eq!(
+            drops.get(),
+            3,
+            "dropping Rc<[T]> must run every element's destructor, same as Rc<T>"
+        );
     }
-}
\ No newline at end of file
+}